@@ -1,10 +1,94 @@
 use std::env::consts::{ARCH, OS};
 use std::fs::File;
-use std::io::Write;
+use std::io::{IsTerminal, Write};
 use std::path::Path;
 use std::process::Command;
 
-use curl::easy::{Easy2, Handler, WriteError};
+use clap::Parser;
+use curl::easy::{Easy, Easy2, Handler, WriteError};
+use fs2::FileExt;
+use sha2::{Digest, Sha256};
+
+/// Command-line arguments. Every field also falls back to an environment
+/// variable, so the installer can be driven headlessly in CI or Docker
+/// builds without any prompt being shown.
+#[derive(Parser, Debug)]
+#[command(version, about = "Install micromamba")]
+struct CliArgs {
+    /// Micromamba root prefix
+    #[arg(long, env = "MICROMAMBA_ROOT_PREFIX")]
+    root_prefix: Option<String>,
+
+    /// Path to install the micromamba binary to
+    #[arg(long, env = "MICROMAMBA_BIN_PATH")]
+    bin_path: Option<String>,
+
+    /// Specific micromamba release tag to install, e.g. `1.5.8-0`. Defaults
+    /// to the `latest` GitHub release.
+    ///
+    /// The field (and so the generated `--micromamba-version` flag) is
+    /// named `micromamba_version` rather than `version` so its arg id
+    /// doesn't collide with the `--version`/`-V` flag clap generates from
+    /// `#[command(version)]`.
+    #[arg(long, env = "MICROMAMBA_VERSION")]
+    micromamba_version: Option<String>,
+
+    /// Which host to download from. Defaults to GitHub; the only other
+    /// supported value is `mamba.pm`.
+    #[arg(long, env = "MICROMAMBA_MIRROR")]
+    mirror: Option<String>,
+
+    /// Initialize micromamba for the current shell
+    #[arg(long, env = "MICROMAMBA_INIT_SHELL")]
+    init_shell: bool,
+
+    /// Skip shell initialization
+    #[arg(long, env = "MICROMAMBA_NO_INIT_SHELL", conflicts_with = "init_shell")]
+    no_init_shell: bool,
+
+    /// Shell to initialize, one of {bash,cmd.exe,dash,fish,posix,powershell,tcsh,xonsh,zsh}
+    #[arg(long, env = "MICROMAMBA_SHELL")]
+    shell: Option<String>,
+
+    /// Accept every default without prompting, for non-interactive use
+    #[arg(short, long, env = "MICROMAMBA_YES")]
+    yes: bool,
+
+    /// Create an environment from this environment.yml after installing
+    #[arg(long, env = "MICROMAMBA_ENVIRONMENT_FILE")]
+    environment_file: Option<String>,
+
+    /// Override the environment name declared in `environment_file`'s `name:` key
+    #[arg(long, env = "MICROMAMBA_NAME")]
+    name: Option<String>,
+
+    /// How to obtain the micromamba binary: always `download` it, require
+    /// one already installed with `system`, or try `system` first and fall
+    /// back to downloading with `auto`
+    #[arg(long, env = "MICROMAMBA_STRATEGY", value_enum, default_value_t = MicromambaStrategy::Download)]
+    strategy: MicromambaStrategy,
+
+    /// Fail fast instead of blocking when another install holds the lock
+    /// on the target exe_path
+    #[arg(long, env = "MICROMAMBA_NO_WAIT")]
+    no_wait: bool,
+
+    /// Expected SHA-256 digest of the downloaded binary. When unset, the
+    /// digest is fetched from the release's `.sha256` asset instead, which
+    /// some mirrors (e.g. `mamba.pm`) do not publish.
+    #[arg(long, env = "MICROMAMBA_SHA256")]
+    sha256: Option<String>,
+}
+
+/// Mirrors the `ORT_STRATEGY=system` idea: whether to always download
+/// micromamba, reuse one that is already installed, or try the latter and
+/// fall back to the former.
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum MicromambaStrategy {
+    Download,
+    System,
+    Auto,
+}
 
 enum OperatingSystem {
     Windows,
@@ -23,10 +107,13 @@ impl OperatingSystem {
     }
 }
 
-/// A handler that writes data to a file.
+/// A handler that writes data to a file while hashing it.
 struct FileHandler {
     /// The file to write data to. This is done in append mode.
     file: File,
+    /// Rolling SHA-256 hash of every byte written so far, so the download
+    /// does not need to be read back from disk to verify it.
+    hasher: Sha256,
 }
 
 impl FileHandler {
@@ -48,16 +135,24 @@ impl FileHandler {
 
         FileHandler {
             file: File::options().append(true).open(path).unwrap(),  // Set write options for executable file
+            hasher: Sha256::new(),
         }
     }
+
+    /// Returns the hex-encoded SHA-256 digest of everything written so far.
+    pub fn digest_hex(&self) -> String {
+        format!("{:x}", self.hasher.clone().finalize())
+    }
 }
 
 impl Handler for FileHandler {
     /// Implement the `write` method of the `Handler` trait.
-    /// This appends the received bytes to `self.file`.
+    /// This appends the received bytes to `self.file` and feeds them into
+    /// the rolling SHA-256 hasher.
     fn write(&mut self, data: &[u8]) -> Result<usize, WriteError> {
         let bytes_received = data.len(); // Count the bytes received
         self.file.write_all(data).unwrap(); // Write the bytes to the file
+        self.hasher.update(data);
         println!("Received {bytes_received} bytes.");
         Ok(bytes_received) // Must return the number of bytes that were passed to data
     }
@@ -68,11 +163,39 @@ struct MicromambaConfig {
     init_shell: bool,
     root_prefix: String,
     shell: Option<String>,
+    /// Expected SHA-256 digest of the downloaded binary. When `None`, the
+    /// digest is instead fetched from the release's `.sha256` asset.
+    sha256: Option<String>,
+    /// Specific micromamba release tag to install, e.g. `1.5.8-0`, read from
+    /// `MICROMAMBA_VERSION`. Defaults to the `latest` GitHub release.
+    version: Option<String>,
+    /// Which host to download from, read from `MICROMAMBA_MIRROR`. Defaults
+    /// to GitHub; the only other supported value is `mamba.pm`.
+    mirror: Option<String>,
+    /// Path to an environment.yml to provision after installing, if any.
+    environment_file: Option<String>,
+    /// Overrides the environment name declared in `environment_file`.
+    env_name: Option<String>,
+    /// Whether to always download micromamba, require an existing install,
+    /// or try the existing install first.
+    strategy: MicromambaStrategy,
+    /// Fail fast instead of blocking when the install lock is already held.
+    no_wait: bool,
 }
 
 impl MicromambaConfig {
-    fn get_root_prefix() -> String {
-        let mut path: String = String::from("~/micromamba/");
+    /// Falls back to an interactive prompt only when `supplied` is `None`
+    /// and we are not running non-interactively; otherwise returns the
+    /// supplied value, or the documented default.
+    fn get_root_prefix(supplied: Option<String>, non_interactive: bool) -> String {
+        let default = String::from("~/micromamba/");
+        if let Some(path) = supplied {
+            return path;
+        }
+        if non_interactive {
+            return default;
+        }
+        let mut path = default;
         println!("Micromamba root prefix? [{}]", path);
         let mut user_input: String = String::new();
         std::io::stdin().read_line(&mut user_input).unwrap();
@@ -83,17 +206,42 @@ impl MicromambaConfig {
         path
     }
 
-    fn init_shell() -> bool {
-        let mut answer = String::new();
-        println!("Initialize micromamba (shell is chosen later)? ([y]/n)");
-        std::io::stdin().read_line(&mut answer).unwrap();
-        match answer.trim() {
-            "" | "y" | "Y" | "yes" => true,
-            "n" | "N" | "no" => false,
-            _ => panic!("Invalid answer: {}", answer),
+    /// `args_init`/`args_no_init` take precedence when set. Otherwise, in
+    /// non-interactive mode, the shell is initialized only when a shell was
+    /// supplied; in interactive mode, an unrecognized answer re-prompts
+    /// instead of panicking.
+    fn init_shell(args_init: bool, args_no_init: bool, non_interactive: bool, shell_known: bool) -> bool {
+        if args_no_init {
+            return false;
+        }
+        if args_init {
+            return true;
+        }
+        if non_interactive {
+            // There is no documented default shell, so only initialize by
+            // default when one was actually supplied; otherwise there is
+            // nothing we could initialize without prompting.
+            return shell_known;
+        }
+        loop {
+            let mut answer = String::new();
+            println!("Initialize micromamba (shell is chosen later)? ([y]/n)");
+            std::io::stdin().read_line(&mut answer).unwrap();
+            match answer.trim() {
+                "" | "y" | "Y" | "yes" => return true,
+                "n" | "N" | "no" => return false,
+                other => println!("Invalid answer: {}. Please enter y or n.", other),
+            }
         }
     }
-    fn ask_for_shell() -> String {
+
+    fn ask_for_shell(supplied: Option<String>, non_interactive: bool) -> Option<String> {
+        if supplied.is_some() {
+            return supplied;
+        }
+        if non_interactive {
+            return None;
+        }
         println!(
             "Select the shell to initialize:\n\
             \n\
@@ -101,14 +249,21 @@ impl MicromambaConfig {
         );
         let mut user_input = String::new();
         std::io::stdin().read_line(&mut user_input).unwrap();
-        String::from(user_input.trim())
+        Some(String::from(user_input.trim()))
     }
 
-    fn get_bin_path(os: &OperatingSystem) -> String {
-        let mut path = match os {
+    fn get_bin_path(os: &OperatingSystem, supplied: Option<String>, non_interactive: bool) -> String {
+        let default = match os {
             OperatingSystem::Windows => String::from("~/micromamba/micromamba.exe"),
             OperatingSystem::Macos | OperatingSystem::Linux => String::from("~/.local/bin/micromamba"),
         };
+        if let Some(path) = supplied {
+            return path;
+        }
+        if non_interactive {
+            return default;
+        }
+        let mut path = default;
         println!("Micromamba binary path? [{}]", path);
         let mut user_input: String = String::new();
         std::io::stdin().read_line(&mut user_input).unwrap();
@@ -118,26 +273,49 @@ impl MicromambaConfig {
         };
         path
     }
-    pub fn new(os: &OperatingSystem) -> MicromambaConfig {
-        let root_prefix: String = MicromambaConfig::get_root_prefix();
-        let init_shell: bool = MicromambaConfig::init_shell();
+
+    pub fn new(os: &OperatingSystem, args: &CliArgs) -> MicromambaConfig {
+        // Stay silent and use defaults whenever `--yes` was passed, or when
+        // stdin is not a TTY (CI, Docker builds, piped input).
+        let non_interactive = args.yes || !std::io::stdin().is_terminal();
+
+        let root_prefix: String =
+            MicromambaConfig::get_root_prefix(args.root_prefix.clone(), non_interactive);
+        let init_shell: bool = MicromambaConfig::init_shell(
+            args.init_shell,
+            args.no_init_shell,
+            non_interactive,
+            args.shell.is_some(),
+        );
         let shell: Option<String> = if init_shell {
-            Some(MicromambaConfig::ask_for_shell())
+            MicromambaConfig::ask_for_shell(args.shell.clone(), non_interactive)
         } else {
             None
         };
-        let exe_path: String = MicromambaConfig::get_bin_path(&os);
-        
+        let exe_path: String =
+            MicromambaConfig::get_bin_path(os, args.bin_path.clone(), non_interactive);
+        // Treat an empty env var the same as an unset one.
+        let version: Option<String> = args.micromamba_version.clone().filter(|v| !v.is_empty());
+        let mirror: Option<String> = args.mirror.clone().filter(|v| !v.is_empty());
+
         MicromambaConfig {
             exe_path,
             init_shell,
             root_prefix,
             shell,
+            sha256: args.sha256.clone(),
+            version,
+            mirror,
+            environment_file: args.environment_file.clone(),
+            env_name: args.name.clone(),
+            strategy: args.strategy.clone(),
+            no_wait: args.no_wait,
         }
     }
 }
 
-fn main() -> () {
+fn main() {
+    let args = CliArgs::parse();
     // Get the current OS as an OperatingSystem type
     let _os: OperatingSystem = match OS {
         "windows" => OperatingSystem::Windows,
@@ -146,58 +324,370 @@ fn main() -> () {
         _ => panic!("Unsupported operating system: {OS}"),
     };
     // Initialize micromamba configuration
-    let _config = MicromambaConfig::new(&_os);
-    download_micromamba_exe(_os, &_config.exe_path).unwrap();
+    let mut _config = MicromambaConfig::new(&_os, &args);
+    _config.exe_path = ensure_micromamba(_os, &_config).unwrap();
     if _config.init_shell {
-        init_micromamba(&_config);
+        init_micromamba(&_config).unwrap();
+    }
+    if _config.environment_file.is_some() {
+        create_environment(&_config).unwrap();
+    }
+}
+
+/// Resolves the micromamba binary to use according to `config.strategy`,
+/// downloading it only when the strategy calls for it. Returns the path to
+/// the binary that should be used for everything afterwards (shell init,
+/// environment creation), which may differ from `config.exe_path` when an
+/// existing install was found elsewhere on `PATH`.
+fn ensure_micromamba(os: OperatingSystem, config: &MicromambaConfig) -> Result<String, String> {
+    let find_compatible = || {
+        find_existing_micromamba(&config.exe_path)
+            .filter(|(_, version)| version_satisfies(version, &config.version))
+    };
+
+    match &config.strategy {
+        MicromambaStrategy::System => find_compatible().map(|(path, _)| path).ok_or_else(|| {
+            String::from(
+                "strategy=system requires an existing micromamba that satisfies the requested \
+                 version, but none was found on PATH or at the configured exe_path",
+            )
+        }),
+        MicromambaStrategy::Auto => match find_compatible() {
+            Some((path, version)) => {
+                println!("Found existing micromamba {} at {}, skipping download", version, path);
+                Ok(path)
+            }
+            None => {
+                // No compatible install found yet, but another installer
+                // racing us for the same exe_path might finish first while
+                // we wait for the lock; download_micromamba_exe rechecks
+                // once it holds the lock before overwriting anything.
+                download_micromamba_exe(
+                    os,
+                    &config.exe_path,
+                    &config.sha256,
+                    &config.version,
+                    &config.mirror,
+                    config.no_wait,
+                    true,
+                )?;
+                Ok(config.exe_path.clone())
+            }
+        },
+        MicromambaStrategy::Download => {
+            download_micromamba_exe(
+                os,
+                &config.exe_path,
+                &config.sha256,
+                &config.version,
+                &config.mirror,
+                config.no_wait,
+                false,
+            )?;
+            Ok(config.exe_path.clone())
+        }
+    }
+}
+
+/// Looks for a usable micromamba at `exe_path`, then on `PATH`, returning
+/// the path that resolved and the version it reports.
+fn find_existing_micromamba(exe_path: &str) -> Option<(String, String)> {
+    [exe_path, "micromamba"]
+        .iter()
+        .find_map(|candidate| micromamba_version(candidate).map(|v| (candidate.to_string(), v)))
+}
+
+/// Runs `<path> --version` and returns its trimmed stdout, or `None` if the
+/// binary does not exist or does not run.
+fn micromamba_version(path: &str) -> Option<String> {
+    let output = Command::new(path).arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Whether an installed version satisfies a requested `MICROMAMBA_VERSION`.
+/// Requested versions may carry a release build suffix (e.g. `1.5.8-0`)
+/// that `micromamba --version` does not report, so only the version part
+/// before the first `-` is compared.
+fn version_satisfies(installed: &str, requested: &Option<String>) -> bool {
+    match requested {
+        None => true,
+        Some(requested) => installed == requested.split('-').next().unwrap_or(requested),
     }
 }
 
-fn download_micromamba_exe(os: OperatingSystem, exe_path: &String) -> Result<(), String> {
+/// An exclusive lock on a sibling `<exe_path>.lock` file, held for the
+/// duration of an install so concurrent installer runs (e.g. parallel CI
+/// matrix jobs) targeting the same `exe_path` don't race on it.
+struct InstallLock {
+    file: File,
+}
+
+impl InstallLock {
+    /// Acquires the lock, blocking until it is free unless `no_wait` is
+    /// set, in which case it fails fast instead.
+    fn acquire(exe_path: &str, no_wait: bool) -> Result<InstallLock, String> {
+        let path = std::path::PathBuf::from(format!("{}.lock", exe_path));
+        if let Some(parent) = path.parent() {
+            if !parent.is_dir() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+        }
+        let file = File::create(&path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+
+        if no_wait {
+            file.try_lock_exclusive().map_err(|_| {
+                format!(
+                    "Another install already holds the lock at {}; pass without --no-wait to wait for it",
+                    path.display()
+                )
+            })?;
+        } else {
+            println!("Waiting for the install lock at {}...", path.display());
+            file.lock_exclusive()
+                .map_err(|e| format!("Failed to acquire lock at {}: {}", path.display(), e))?;
+        }
+
+        Ok(InstallLock { file })
+    }
+}
+
+impl Drop for InstallLock {
+    /// Releases the lock. The lock file itself is deliberately left on
+    /// disk: unlinking it here would race with a process already blocked
+    /// in `lock_exclusive()` on this inode, which could then have the path
+    /// recreated out from under it by a third process and end up locking a
+    /// different inode than the one the lock was meant to guard. Leaving
+    /// the lock file in place is the standard flock-based-lockfile pattern.
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
+
+fn download_micromamba_exe(
+    os: OperatingSystem,
+    exe_path: &String,
+    sha256: &Option<String>,
+    version: &Option<String>,
+    mirror: &Option<String>,
+    no_wait: bool,
+    skip_if_compatible: bool,
+) -> Result<(), String> {
+    // Hold an exclusive lock across the whole download-and-verify sequence
+    // so two installer invocations targeting the same exe_path don't race.
+    let _lock = InstallLock::acquire(exe_path, no_wait)?;
+
+    // MicromambaStrategy::Auto already checked for a compatible install before
+    // taking the lock, but a concurrent installer may have just finished
+    // writing exe_path while we were blocked waiting for it. Re-check now
+    // that we hold the lock so we don't clobber what it just installed.
+    if skip_if_compatible {
+        if let Some(installed_version) = micromamba_version(exe_path) {
+            if version_satisfies(&installed_version, version) {
+                println!(
+                    "Found existing micromamba {} at {} after acquiring the install lock, skipping download",
+                    installed_version, exe_path
+                );
+                return Ok(());
+            }
+        }
+    }
+
     // Determine what download URL we should query, based on OS and architecture
-    let os_arch: String = determine_os_arch(&os);
-    let url: String = format!(
-        "https://github.com/mamba-org/micromamba-releases/releases/latest/download/micromamba-{}",
-        os_arch
-    );
+    let os_arch: String = determine_os_arch(&os)?;
+    let url: String = build_download_url(&os_arch, version, mirror);
     println!("Sending request to {}", &url);
 
     // Download the executable
-    let mut easy = Easy2::new(FileHandler::new(&exe_path));
+    let mut easy = Easy2::new(FileHandler::new(exe_path));
     easy.get(true).unwrap();
     easy.follow_location(true).unwrap();
     easy.url(&url).unwrap();
-    easy.perform().unwrap();
+    if let Err(e) = easy.perform() {
+        std::fs::remove_file(exe_path).unwrap();
+        return Err(format!("Failed to download {}: {}", url, e));
+    }
 
     // Check the response code
     let response_code: u32 = easy.response_code().unwrap();
-    match response_code {
-        200 => Ok(()),
-        _ => Err(format!("Got response code {}", response_code)),
+    if response_code != 200 {
+        std::fs::remove_file(exe_path).unwrap();
+        return Err(format!("Got response code {}", response_code));
+    }
+
+    // Verify the integrity of the downloaded bytes before leaving the
+    // executable in place.
+    let expected_sha256 = match sha256 {
+        Some(expected) => expected.to_lowercase(),
+        None => fetch_expected_sha256(&format!("{}.sha256", url))?,
+    };
+    let actual_sha256 = easy.get_ref().digest_hex();
+    if actual_sha256 != expected_sha256 {
+        std::fs::remove_file(exe_path).unwrap();
+        return Err(format!(
+            "SHA-256 mismatch for {}: expected {}, got {}",
+            exe_path, expected_sha256, actual_sha256
+        ));
+    }
+
+    Ok(())
+}
+
+/// Fetches a small text resource, such as a `.sha256` checksum file, and
+/// returns the first whitespace-separated token of its body (the digest
+/// itself, ignoring any trailing filename `sha256sum` output includes).
+fn fetch_expected_sha256(url: &str) -> Result<String, String> {
+    let mut data: Vec<u8> = Vec::new();
+    let mut easy = Easy::new();
+    easy.get(true).unwrap();
+    easy.follow_location(true).unwrap();
+    easy.url(url).unwrap();
+    {
+        let mut transfer = easy.transfer();
+        transfer
+            .write_function(|chunk| {
+                data.extend_from_slice(chunk);
+                Ok(chunk.len())
+            })
+            .unwrap();
+        transfer
+            .perform()
+            .map_err(|e| format!("Failed to fetch {}: {}", url, e))?;
+    }
+
+    let response_code: u32 = easy.response_code().unwrap();
+    if response_code != 200 {
+        return Err(format!(
+            "Got response code {} while fetching {}",
+            response_code, url
+        ));
+    }
+
+    let body = String::from_utf8(data).map_err(|e| e.to_string())?;
+    body.split_whitespace()
+        .next()
+        .map(|digest| digest.to_lowercase())
+        .ok_or_else(|| format!("Empty checksum file at {}", url))
+}
+
+/// Build the URL to download the micromamba executable from, honouring an
+/// optional pinned `version` (tag) and an optional `mirror`.
+///
+/// With no `version`, the GitHub mirror installs the `latest` release as
+/// before. A `version` pins a specific tag, e.g. `1.5.8-0`. The `mamba.pm`
+/// mirror uses `https://micro.mamba.pm/api/micromamba/{os_arch}/{version}`,
+/// which also accepts `latest` in place of a version.
+fn build_download_url(os_arch: &str, version: &Option<String>, mirror: &Option<String>) -> String {
+    match mirror.as_deref() {
+        Some("mamba.pm") => {
+            let version = version.as_deref().unwrap_or("latest");
+            format!("https://micro.mamba.pm/api/micromamba/{}/{}", os_arch, version)
+        }
+        _ => match version {
+            Some(version) => format!(
+                "https://github.com/mamba-org/micromamba-releases/releases/download/{}/micromamba-{}",
+                version, os_arch
+            ),
+            None => format!(
+                "https://github.com/mamba-org/micromamba-releases/releases/latest/download/micromamba-{}",
+                os_arch
+            ),
+        },
     }
 }
 
 /// Determine the ${OS}-${ARCH} part of the GitHub download URL.
-fn determine_os_arch(os: &OperatingSystem) -> String {
-    let mut os_arch = os.as_string();
-    os_arch += "-";
-
-    os_arch += match ARCH {
-        "x86_64" => "64",
-        "arm" => "arm64",
-        _ => panic!("Unsupported architecture {:?}", ARCH),
+///
+/// The upstream micromamba-releases project only publishes a fixed set of
+/// `(OS, ARCH)` combinations, and the asset suffix does not always match
+/// Rust's own `ARCH` string (notably `aarch64`, which becomes `arm64` on
+/// macOS but stays `aarch64` on Linux). Combinations with no published
+/// asset, such as Windows on ARM, are rejected with an error rather than
+/// producing a URL that 404s.
+fn determine_os_arch(os: &OperatingSystem) -> Result<String, String> {
+    let arch = match (os, ARCH) {
+        (OperatingSystem::Macos, "aarch64") => "arm64",
+        (OperatingSystem::Linux, "aarch64") => "aarch64",
+        (OperatingSystem::Linux, "powerpc64") => "ppc64le",
+        (OperatingSystem::Windows, "x86_64")
+        | (OperatingSystem::Macos, "x86_64")
+        | (OperatingSystem::Linux, "x86_64") => "64",
+        _ => {
+            return Err(format!(
+                "No micromamba release is published for {}-{}",
+                os.as_string(),
+                ARCH
+            ))
+        }
     };
-    os_arch
+    Ok(format!("{}-{}", os.as_string(), arch))
 }
 
-fn init_micromamba(config: &MicromambaConfig) {
+fn init_micromamba(config: &MicromambaConfig) -> Result<(), String> {
     println!("Initializing micromamba for current shell with");
     println!("{}", &config.exe_path);
     let shell: &String = match &config.shell {
         Some(i) => i,
-        None => panic!("Tried shell initialization without a shell.")
+        None => {
+            return Err(String::from(
+                "Asked to initialize the shell but no shell was supplied; pass --shell or MICROMAMBA_SHELL",
+            ))
+        }
     };
     let mut micromamba = Command::new(&config.exe_path);
     micromamba.arg("shell").arg("init").arg("--prefix").arg(&config.root_prefix).arg("--shell").arg(shell);
-    micromamba.status().unwrap();
+    micromamba.status().map_err(|e| format!("Failed to run {}: {}", config.exe_path, e))?;
+    Ok(())
+}
+
+/// Provisions a conda environment from `config.environment_file` by running
+/// `micromamba create`. The environment name is only used for logging; the
+/// install still targets `config.root_prefix`, matching `init_micromamba`.
+fn create_environment(config: &MicromambaConfig) -> Result<(), String> {
+    let env_file: &String = match &config.environment_file {
+        Some(f) => f,
+        None => return Ok(()),
+    };
+    let contents = std::fs::read_to_string(env_file)
+        .map_err(|e| format!("Failed to read {}: {}", env_file, e))?;
+    let name = config
+        .env_name
+        .clone()
+        .or_else(|| parse_environment_name(&contents));
+    match &name {
+        Some(name) => println!("Creating environment '{}' from {}", name, env_file),
+        None => println!("Creating environment from {}", env_file),
+    }
+
+    let mut micromamba = Command::new(&config.exe_path);
+    micromamba
+        .arg("create")
+        .arg("-y")
+        .arg("--prefix")
+        .arg(&config.root_prefix)
+        .arg("--file")
+        .arg(env_file);
+    let status = micromamba
+        .status()
+        .map_err(|e| format!("Failed to run {}: {}", config.exe_path, e))?;
+    if !status.success() {
+        return Err(format!("micromamba create exited with {}", status));
+    }
+    Ok(())
+}
+
+/// Pulls the `name:` key out of an environment.yml well enough to log it,
+/// without pulling in a full YAML parser for this single field.
+fn parse_environment_name(contents: &str) -> Option<String> {
+    for line in contents.lines() {
+        if let Some(rest) = line.trim().strip_prefix("name:") {
+            let name = rest.trim().trim_matches(|c| c == '\'' || c == '"');
+            if !name.is_empty() {
+                return Some(String::from(name));
+            }
+        }
+    }
+    None
 }